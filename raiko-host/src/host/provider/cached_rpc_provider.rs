@@ -12,21 +12,76 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod codec;
+mod lru;
+
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+pub use codec::CacheCodec;
 
 use alloy_rpc_types::EIP1186AccountProofResponse;
 use anyhow::Result;
 use ethers_core::types::{Block, Bytes, Log, Transaction, TransactionReceipt, H256, U256};
+use tracing::warn;
 
 use super::{
-    file_provider::FileProvider, rpc_provider::RpcProvider, AccountQuery, BlockQuery,
-    GetBlobsResponse, MutProvider, ProofQuery, Provider, StorageQuery,
+    file_provider::{CacheError, FileProvider},
+    rpc_provider::RpcProvider,
+    AccountQuery, BlockQuery, GetBlobsResponse, MutProvider, ProofQuery, Provider, StorageQuery,
 };
-use crate::host::provider::LogsQuery;
+use crate::host::provider::{LogsQuery, TxQuery};
+use lru::LruCache;
+
+/// Default number of entries kept per query type in the in-memory LRU tier.
+const DEFAULT_LRU_CAPACITY: usize = 1024;
+
+/// What to do when the on-disk cache fails its checksum check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Refuse to start; the caller must investigate or remove the file.
+    FailOnCorruption,
+    /// Rename the corrupt file to `cache_path` with its extension replaced by
+    /// `bak` (e.g. `cache.json` becomes `cache.bak`) and start from an empty
+    /// cache.
+    #[default]
+    QuarantineAndRebuild,
+    /// Ignore the corruption and start from an empty cache, leaving the
+    /// broken file in place (it will be overwritten on the next `save()`).
+    TreatAsEmpty,
+}
+
+/// How often [`CachedRpcProvider::finalized_block`] is allowed to re-query
+/// the finalized block number from RPC, rather than reusing the last value.
+const DEFAULT_FINALITY_REFRESH: Duration = Duration::from_secs(60);
+
+/// Time-to-live applied to cache entries that aren't keyed to a specific
+/// block number (e.g. a transaction looked up by hash alone), since finality
+/// doesn't apply to them.
+const DEFAULT_UNSCOPED_TTL: Duration = Duration::from_secs(60);
 
 pub struct CachedRpcProvider {
     cache: FileProvider,
     rpc: RpcProvider,
+
+    lru_full_block: LruCache<BlockQuery, Block<Transaction>>,
+    lru_partial_block: LruCache<BlockQuery, Block<H256>>,
+    lru_block_receipts: LruCache<BlockQuery, Vec<TransactionReceipt>>,
+    lru_proof: LruCache<ProofQuery, EIP1186AccountProofResponse>,
+    lru_transaction_count: LruCache<AccountQuery, U256>,
+    lru_balance: LruCache<AccountQuery, U256>,
+    lru_code: LruCache<AccountQuery, Bytes>,
+    lru_storage: LruCache<StorageQuery, H256>,
+    lru_logs: LruCache<LogsQuery, Vec<Log>>,
+    lru_transaction: LruCache<TxQuery, (Transaction, Instant)>,
+    lru_blob: LruCache<u64, GetBlobsResponse>,
+
+    /// Highest block number known to be finalized, cached for
+    /// `finality_refresh` between RPC round-trips.
+    finalized_block: Option<u64>,
+    finalized_block_checked_at: Option<Instant>,
+    finality_refresh: Duration,
+    unscoped_ttl: Duration,
 }
 
 impl CachedRpcProvider {
@@ -35,13 +90,127 @@ impl CachedRpcProvider {
         rpc_url: String,
         beacon_rpc_url: Option<String>,
     ) -> Result<Self> {
-        let cache = match FileProvider::from_file(&cache_path) {
+        Self::with_lru_capacity(cache_path, rpc_url, beacon_rpc_url, DEFAULT_LRU_CAPACITY)
+    }
+
+    /// Like [`CachedRpcProvider::new`], but with an explicit capacity for the
+    /// in-memory LRU tier (applied per query type, not in aggregate).
+    pub fn with_lru_capacity(
+        cache_path: PathBuf,
+        rpc_url: String,
+        beacon_rpc_url: Option<String>,
+        lru_capacity: usize,
+    ) -> Result<Self> {
+        Self::with_options(
+            cache_path,
+            rpc_url,
+            beacon_rpc_url,
+            lru_capacity,
+            CachePolicy::default(),
+            None,
+        )
+    }
+
+    /// Like [`CachedRpcProvider::new`], with full control over the in-memory
+    /// LRU capacity, the policy applied when the on-disk cache fails its
+    /// integrity check (see [`CachePolicy`]), and the serialization backend
+    /// used to persist it (see [`CacheCodec`]). Pass `None` for `codec` to
+    /// infer it from `cache_path`'s extension.
+    pub fn with_options(
+        cache_path: PathBuf,
+        rpc_url: String,
+        beacon_rpc_url: Option<String>,
+        lru_capacity: usize,
+        cache_policy: CachePolicy,
+        codec: Option<CacheCodec>,
+    ) -> Result<Self> {
+        let codec = codec.unwrap_or_else(|| CacheCodec::from_extension(&cache_path));
+        let cache = match FileProvider::from_file(&cache_path, codec) {
             Ok(provider) => provider,
-            Err(_) => FileProvider::empty(cache_path),
+            Err(CacheError::Missing) => FileProvider::empty(cache_path, codec),
+            Err(CacheError::Corrupted) => match cache_policy {
+                CachePolicy::FailOnCorruption => {
+                    anyhow::bail!("cache file {} is corrupted", cache_path.display())
+                }
+                CachePolicy::QuarantineAndRebuild => {
+                    let quarantined = cache_path.with_extension("bak");
+                    std::fs::rename(&cache_path, &quarantined)?;
+                    FileProvider::empty(cache_path, codec)
+                }
+                CachePolicy::TreatAsEmpty => FileProvider::empty(cache_path, codec),
+            },
+            Err(CacheError::Io(err)) => return Err(err.into()),
         };
         let rpc = RpcProvider::new(rpc_url, beacon_rpc_url)?;
 
-        Ok(CachedRpcProvider { cache, rpc })
+        Ok(CachedRpcProvider {
+            cache,
+            rpc,
+            lru_full_block: LruCache::new(lru_capacity),
+            lru_partial_block: LruCache::new(lru_capacity),
+            lru_block_receipts: LruCache::new(lru_capacity),
+            lru_proof: LruCache::new(lru_capacity),
+            lru_transaction_count: LruCache::new(lru_capacity),
+            lru_balance: LruCache::new(lru_capacity),
+            lru_code: LruCache::new(lru_capacity),
+            lru_storage: LruCache::new(lru_capacity),
+            lru_logs: LruCache::new(lru_capacity),
+            lru_transaction: LruCache::new(lru_capacity),
+            lru_blob: LruCache::new(lru_capacity),
+            finalized_block: None,
+            finalized_block_checked_at: None,
+            finality_refresh: DEFAULT_FINALITY_REFRESH,
+            unscoped_ttl: DEFAULT_UNSCOPED_TTL,
+        })
+    }
+
+    /// Returns the highest finalized block number, re-querying RPC only if
+    /// `finality_refresh` has elapsed since the last check. If the refresh
+    /// call fails but a previous value is on hand, that stale value is
+    /// reused (with a log) rather than failing outright, so a temporarily
+    /// unreachable endpoint can't take down reads that don't even need RPC.
+    fn finalized_block(&mut self) -> Result<u64> {
+        let stale = match self.finalized_block_checked_at {
+            Some(checked_at) => checked_at.elapsed() >= self.finality_refresh,
+            None => true,
+        };
+
+        if stale {
+            match self.rpc.get_finalized_block_number() {
+                Ok(finalized) => {
+                    self.finalized_block = Some(finalized);
+                    self.finalized_block_checked_at = Some(Instant::now());
+                }
+                Err(err) => match self.finalized_block {
+                    Some(finalized) => {
+                        warn!(
+                            "failed to refresh finalized block number, reusing last known \
+                             value {finalized}: {err}"
+                        );
+                    }
+                    None => return Err(err),
+                },
+            }
+        }
+
+        Ok(self.finalized_block.expect("just populated above"))
+    }
+
+    /// Whether `block_no` is at or behind the last known finalized block,
+    /// i.e. whether it's safe to trust a cached value for it. Only called
+    /// once a cache/LRU hit has actually been found, so a query that misses
+    /// the cache entirely never pays for a finality check it doesn't need.
+    /// If finality can't be determined at all (no previous value and RPC is
+    /// unreachable), the hit is conservatively treated as not finalized,
+    /// falling through to a live fetch instead of failing the whole read.
+    fn is_finalized(&mut self, block_no: u64) -> bool {
+        match self.finalized_block() {
+            Ok(finalized) => block_no <= finalized,
+            Err(err) => {
+                warn!("unable to determine finalized block, treating {block_no} as not finalized: {err}");
+                false
+            }
+        }
     }
 }
 
@@ -51,125 +220,213 @@ impl Provider for CachedRpcProvider {
     }
 
     fn get_full_block(&mut self, query: &BlockQuery) -> Result<Block<Transaction>> {
-        let cache_out = self.cache.get_full_block(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_full_block.get(query).cloned() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_full_block(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_full_block.insert(query.clone(), out.clone());
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_full_block(query)?;
         self.cache.insert_full_block(query.clone(), out.clone());
+        self.lru_full_block.insert(query.clone(), out.clone());
 
         Ok(out)
     }
 
     fn get_partial_block(&mut self, query: &BlockQuery) -> Result<Block<H256>> {
-        let cache_out = self.cache.get_partial_block(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_partial_block.get(query).cloned() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_partial_block(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_partial_block.insert(query.clone(), out.clone());
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_partial_block(query)?;
         self.cache.insert_partial_block(query.clone(), out.clone());
+        self.lru_partial_block.insert(query.clone(), out.clone());
 
         Ok(out)
     }
 
     fn get_block_receipts(&mut self, query: &BlockQuery) -> Result<Vec<TransactionReceipt>> {
-        let cache_out = self.cache.get_block_receipts(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_block_receipts.get(query).cloned() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_block_receipts(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_block_receipts.insert(query.clone(), out.clone());
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_block_receipts(query)?;
         self.cache.insert_block_receipts(query.clone(), out.clone());
+        self.lru_block_receipts.insert(query.clone(), out.clone());
 
         Ok(out)
     }
 
     fn get_proof(&mut self, query: &ProofQuery) -> Result<EIP1186AccountProofResponse> {
-        let cache_out = self.cache.get_proof(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_proof.get(query).cloned() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_proof(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_proof.insert(query.clone(), out.clone());
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_proof(query)?;
         self.cache.insert_proof(query.clone(), out.clone());
+        self.lru_proof.insert(query.clone(), out.clone());
 
         Ok(out)
     }
 
     fn get_transaction_count(&mut self, query: &AccountQuery) -> Result<U256> {
-        let cache_out = self.cache.get_transaction_count(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_transaction_count.get(query).copied() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_transaction_count(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_transaction_count.insert(query.clone(), out);
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_transaction_count(query)?;
         self.cache.insert_transaction_count(query.clone(), out);
+        self.lru_transaction_count.insert(query.clone(), out);
 
         Ok(out)
     }
 
     fn get_balance(&mut self, query: &AccountQuery) -> Result<U256> {
-        let cache_out = self.cache.get_balance(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_balance.get(query).copied() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_balance(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_balance.insert(query.clone(), out);
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_balance(query)?;
         self.cache.insert_balance(query.clone(), out);
+        self.lru_balance.insert(query.clone(), out);
 
         Ok(out)
     }
 
     fn get_code(&mut self, query: &AccountQuery) -> Result<Bytes> {
-        let cache_out = self.cache.get_code(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_code.get(query).cloned() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_code(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_code.insert(query.clone(), out.clone());
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_code(query)?;
         self.cache.insert_code(query.clone(), out.clone());
+        self.lru_code.insert(query.clone(), out.clone());
 
         Ok(out)
     }
 
     fn get_storage(&mut self, query: &StorageQuery) -> Result<H256> {
-        let cache_out = self.cache.get_storage(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        if let Some(out) = self.lru_storage.get(query).copied() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_storage(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_storage.insert(query.clone(), out);
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_storage(query)?;
         self.cache.insert_storage(query.clone(), out);
+        self.lru_storage.insert(query.clone(), out);
 
         Ok(out)
     }
 
     fn get_logs(&mut self, query: &LogsQuery) -> Result<Vec<Log>> {
-        let cache_out = self.cache.get_logs(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        // Logs are exactly the kind of data a reorg silently invalidates
+        // (removed or duplicated), so they get the same bypass-if-not-
+        // finalized treatment as every other block-scoped query.
+        if let Some(out) = self.lru_logs.get(query).cloned() {
+            if self.is_finalized(query.block_no) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_logs(query) {
+            if self.is_finalized(query.block_no) {
+                self.lru_logs.insert(query.clone(), out.clone());
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_logs(query)?;
         self.cache.insert_logs(query.clone(), out.clone());
+        self.lru_logs.insert(query.clone(), out.clone());
 
         Ok(out)
     }
 
     fn get_transaction(&mut self, query: &super::TxQuery) -> Result<Transaction> {
-        let cache_out = self.cache.get_transaction(query);
-        if cache_out.is_ok() {
-            return cache_out;
+        // A transaction pinned to a block is as trustworthy as that block;
+        // one that isn't (e.g. looked up by hash alone, before we know which
+        // block it landed in) gets a plain TTL instead, since there's no
+        // block height to check against finality.
+        if let Some((out, inserted_at)) = self.lru_transaction.get(query).cloned() {
+            let trust_cache = match query.block_no {
+                Some(block_no) => self.is_finalized(block_no),
+                None => inserted_at.elapsed() < self.unscoped_ttl,
+            };
+            if trust_cache {
+                return Ok(out);
+            }
         }
 
-        // Search cached block for target Tx
+        // The on-disk cache carries no insertion timestamp, so it can only
+        // be trusted for block-scoped queries (gated below by
+        // `is_finalized`); an unscoped query falling through here must go to
+        // RPC rather than being trusted forever from disk.
         if let Some(block_no) = query.block_no {
-            if let Ok(block) = self.cache.get_full_block(&BlockQuery { block_no }) {
-                for tx in block.transactions {
-                    if tx.hash == query.tx_hash {
-                        return Ok(tx.clone());
+            if let Ok(out) = self.cache.get_transaction(query) {
+                if self.is_finalized(block_no) {
+                    self.lru_transaction
+                        .insert(query.clone(), (out.clone(), Instant::now()));
+                    return Ok(out);
+                }
+            } else if let Ok(block) = self.cache.get_full_block(&BlockQuery { block_no }) {
+                if self.is_finalized(block_no) {
+                    for tx in block.transactions {
+                        if tx.hash == query.tx_hash {
+                            self.lru_transaction
+                                .insert(query.clone(), (tx.clone(), Instant::now()));
+                            return Ok(tx.clone());
+                        }
                     }
                 }
             }
@@ -177,19 +434,248 @@ impl Provider for CachedRpcProvider {
 
         let out = self.rpc.get_transaction(query)?;
         self.cache.insert_transaction(query.clone(), out.clone());
+        self.lru_transaction
+            .insert(query.clone(), (out.clone(), Instant::now()));
 
         Ok(out)
     }
 
     fn get_blob_data(&mut self, block_id: u64) -> Result<GetBlobsResponse> {
-        let cache_out = self.cache.get_blob_data(block_id);
-        if cache_out.is_ok() {
-            return cache_out;
+        // Blob data is keyed to a block just like receipts or proofs, so it
+        // is equally reorg-sensitive and needs the same finality gate.
+        if let Some(out) = self.lru_blob.get(&block_id).cloned() {
+            if self.is_finalized(block_id) {
+                return Ok(out);
+            }
+        } else if let Ok(out) = self.cache.get_blob_data(block_id) {
+            if self.is_finalized(block_id) {
+                self.lru_blob.insert(block_id, out.clone());
+                return Ok(out);
+            }
         }
 
         let out = self.rpc.get_blob_data(block_id)?;
         self.cache.insert_blob(block_id, out.clone());
+        self.lru_blob.insert(block_id, out.clone());
 
         Ok(out)
     }
+
+    // The batch methods below override the `Provider` trait's default
+    // loop-over-singles implementation. Each splits the requested keys into
+    // cache/LRU hits (served immediately, no RPC round-trip) and a residual
+    // set, which is forwarded to `self.rpc` as a single batched call so a
+    // witness covering thousands of accounts costs O(1) RPC round-trips
+    // instead of O(n).
+
+    fn get_proofs(&mut self, queries: &[ProofQuery]) -> Result<Vec<EIP1186AccountProofResponse>> {
+        let mut out = vec![None; queries.len()];
+        let mut missing = Vec::new();
+
+        for (i, query) in queries.iter().enumerate() {
+            if let Some(hit) = self.lru_proof.get(query).cloned() {
+                if self.is_finalized(query.block_no) {
+                    out[i] = Some(hit);
+                    continue;
+                }
+            } else if let Ok(hit) = self.cache.get_proof(query) {
+                if self.is_finalized(query.block_no) {
+                    self.lru_proof.insert(query.clone(), hit.clone());
+                    out[i] = Some(hit);
+                    continue;
+                }
+            }
+            missing.push(i);
+        }
+
+        if !missing.is_empty() {
+            let missing_queries: Vec<ProofQuery> =
+                missing.iter().map(|&i| queries[i].clone()).collect();
+            let fetched = self.rpc.get_proofs(&missing_queries)?;
+            check_batch_len(missing.len(), fetched.len())?;
+
+            for (&i, proof) in missing.iter().zip(fetched.into_iter()) {
+                self.cache.insert_proof(queries[i].clone(), proof.clone());
+                self.lru_proof.insert(queries[i].clone(), proof.clone());
+                out[i] = Some(proof);
+            }
+        }
+
+        collect_batch_results(out)
+    }
+
+    fn get_storage_many(&mut self, queries: &[StorageQuery]) -> Result<Vec<H256>> {
+        let mut out = vec![None; queries.len()];
+        let mut missing = Vec::new();
+
+        for (i, query) in queries.iter().enumerate() {
+            if let Some(hit) = self.lru_storage.get(query).copied() {
+                if self.is_finalized(query.block_no) {
+                    out[i] = Some(hit);
+                    continue;
+                }
+            } else if let Ok(hit) = self.cache.get_storage(query) {
+                if self.is_finalized(query.block_no) {
+                    self.lru_storage.insert(query.clone(), hit);
+                    out[i] = Some(hit);
+                    continue;
+                }
+            }
+            missing.push(i);
+        }
+
+        if !missing.is_empty() {
+            let missing_queries: Vec<StorageQuery> =
+                missing.iter().map(|&i| queries[i].clone()).collect();
+            let fetched = self.rpc.get_storage_many(&missing_queries)?;
+            check_batch_len(missing.len(), fetched.len())?;
+
+            for (&i, value) in missing.iter().zip(fetched.into_iter()) {
+                self.cache.insert_storage(queries[i].clone(), value);
+                self.lru_storage.insert(queries[i].clone(), value);
+                out[i] = Some(value);
+            }
+        }
+
+        collect_batch_results(out)
+    }
+
+    fn get_codes(&mut self, queries: &[AccountQuery]) -> Result<Vec<Bytes>> {
+        let mut out = vec![None; queries.len()];
+        let mut missing = Vec::new();
+
+        for (i, query) in queries.iter().enumerate() {
+            if let Some(hit) = self.lru_code.get(query).cloned() {
+                if self.is_finalized(query.block_no) {
+                    out[i] = Some(hit);
+                    continue;
+                }
+            } else if let Ok(hit) = self.cache.get_code(query) {
+                if self.is_finalized(query.block_no) {
+                    self.lru_code.insert(query.clone(), hit.clone());
+                    out[i] = Some(hit);
+                    continue;
+                }
+            }
+            missing.push(i);
+        }
+
+        if !missing.is_empty() {
+            let missing_queries: Vec<AccountQuery> =
+                missing.iter().map(|&i| queries[i].clone()).collect();
+            let fetched = self.rpc.get_codes(&missing_queries)?;
+            check_batch_len(missing.len(), fetched.len())?;
+
+            for (&i, code) in missing.iter().zip(fetched.into_iter()) {
+                self.cache.insert_code(queries[i].clone(), code.clone());
+                self.lru_code.insert(queries[i].clone(), code.clone());
+                out[i] = Some(code);
+            }
+        }
+
+        collect_batch_results(out)
+    }
+}
+
+/// Checks that a batched RPC call returned exactly as many results as were
+/// requested, rather than silently trusting a partial response.
+fn check_batch_len(expected: usize, actual: usize) -> Result<()> {
+    if expected != actual {
+        anyhow::bail!("RPC batch returned {actual} results for {expected} requested queries");
+    }
+    Ok(())
+}
+
+/// Turns the per-query `Option` slots filled in by a batch lookup into a
+/// plain `Vec`, failing loudly instead of panicking if any slot was never
+/// filled (which would indicate a bug in the cache/miss split above, since
+/// [`check_batch_len`] already guards against a short RPC response).
+fn collect_batch_results<T>(out: Vec<Option<T>>) -> Result<Vec<T>> {
+    out.into_iter()
+        .enumerate()
+        .map(|(i, v)| v.ok_or_else(|| anyhow::anyhow!("query {i} was never resolved")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::provider::file_provider::FileProvider;
+
+    fn write_corrupt_cache(path: &std::path::Path) {
+        let provider = FileProvider::empty(path.to_path_buf(), CacheCodec::Json);
+        provider.save().unwrap();
+
+        let mut raw = std::fs::read(path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0xff;
+        std::fs::write(path, &raw).unwrap();
+    }
+
+    #[test]
+    fn fail_on_corruption_bails() {
+        let path = std::env::temp_dir().join(format!(
+            "raiko_cache_policy_fail_{}.json",
+            std::process::id()
+        ));
+        write_corrupt_cache(&path);
+
+        let result = CachedRpcProvider::with_options(
+            path.clone(),
+            "http://localhost:8545".to_string(),
+            None,
+            DEFAULT_LRU_CAPACITY,
+            CachePolicy::FailOnCorruption,
+            None,
+        );
+        assert!(result.is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn quarantine_and_rebuild_renames_corrupt_file() {
+        let path = std::env::temp_dir().join(format!(
+            "raiko_cache_policy_quarantine_{}.json",
+            std::process::id()
+        ));
+        write_corrupt_cache(&path);
+
+        let result = CachedRpcProvider::with_options(
+            path.clone(),
+            "http://localhost:8545".to_string(),
+            None,
+            DEFAULT_LRU_CAPACITY,
+            CachePolicy::QuarantineAndRebuild,
+            None,
+        );
+        assert!(result.is_ok());
+        assert!(!path.exists());
+
+        let quarantined = path.with_extension("bak");
+        assert!(quarantined.exists());
+        std::fs::remove_file(&quarantined).ok();
+    }
+
+    #[test]
+    fn treat_as_empty_leaves_corrupt_file_in_place() {
+        let path = std::env::temp_dir().join(format!(
+            "raiko_cache_policy_treat_as_empty_{}.json",
+            std::process::id()
+        ));
+        write_corrupt_cache(&path);
+
+        let result = CachedRpcProvider::with_options(
+            path.clone(),
+            "http://localhost:8545".to_string(),
+            None,
+            DEFAULT_LRU_CAPACITY,
+            CachePolicy::TreatAsEmpty,
+            None,
+        );
+        assert!(result.is_ok());
+        assert!(path.exists());
+
+        std::fs::remove_file(&path).ok();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,128 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// A bounded, in-memory least-recently-used cache.
+///
+/// Recency is tracked with a `VecDeque` of keys, most-recently-used at the
+/// back. This keeps the implementation simple at the cost of an `O(n)`
+/// reshuffle per access, which is acceptable for the small per-query-type
+/// capacities this is used with.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, V>,
+    recency: VecDeque<K>,
+}
+
+impl<K: Clone + Eq + Hash, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            capacity,
+            entries: HashMap::new(),
+            recency: VecDeque::new(),
+        }
+    }
+
+    /// Returns the cached value for `key`, promoting it to most-recently-used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self.entries.contains_key(key) {
+            return None;
+        }
+        self.touch(key);
+        self.entries.get(key)
+    }
+
+    /// Inserts `value` under `key`, evicting the least-recently-used entry if
+    /// the cache is at capacity.
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.entries.contains_key(&key) {
+            self.touch(&key);
+            self.entries.insert(key, value);
+            return;
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() >= self.capacity {
+            if let Some(lru_key) = self.recency.pop_front() {
+                self.entries.remove(&lru_key);
+            }
+        }
+
+        self.recency.push_back(key.clone());
+        self.entries.insert(key, value);
+    }
+
+    fn touch(&mut self, key: &K) {
+        if let Some(pos) = self.recency.iter().position(|k| k == key) {
+            let key = self.recency.remove(pos).unwrap();
+            self.recency.push_back(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LruCache;
+
+    #[test]
+    fn zero_capacity_never_retains_entries() {
+        let mut cache: LruCache<u32, u32> = LruCache::new(0);
+        cache.insert(1, 10);
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_on_overflow() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        cache.insert(3, "c");
+
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&2), Some(&"b"));
+        assert_eq!(cache.get(&3), Some(&"c"));
+    }
+
+    #[test]
+    fn get_promotes_entry_to_most_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+
+        // Touch `1` so `2` becomes the least-recently-used entry.
+        assert_eq!(cache.get(&1), Some(&"a"));
+
+        cache.insert(3, "c");
+
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.get(&3), Some(&"c"));
+    }
+
+    #[test]
+    fn insert_overwrites_existing_key_without_evicting() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        cache.insert(1, "a2");
+
+        assert_eq!(cache.get(&1), Some(&"a2"));
+        assert_eq!(cache.get(&2), Some(&"b"));
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use alloy_rpc_types::EIP1186AccountProofResponse;
+use anyhow::Result;
+use ethers_core::types::{Block, Bytes, Log, Transaction, TransactionReceipt, H256, U256};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::cached_rpc_provider::CacheCodec;
+use super::{
+    AccountQuery, BlockQuery, GetBlobsResponse, LogsQuery, MutProvider, Provider, ProofQuery,
+    StorageQuery, TxQuery,
+};
+
+const CHECKSUM_LEN: usize = 32;
+
+/// Why a cache file at a given path could not be loaded.
+#[derive(Debug)]
+pub enum CacheError {
+    /// The file does not exist; this is a cold start, not corruption.
+    Missing,
+    /// The file exists but its checksum does not match its contents, or it
+    /// otherwise fails to decode.
+    Corrupted,
+    /// Any other I/O failure (permissions, etc.).
+    Io(std::io::Error),
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Missing => write!(f, "cache file does not exist"),
+            CacheError::Corrupted => write!(f, "cache file failed its integrity check"),
+            CacheError::Io(err) => write!(f, "cache file I/O error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+impl From<std::io::Error> for CacheError {
+    fn from(err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            CacheError::Missing
+        } else {
+            CacheError::Io(err)
+        }
+    }
+}
+
+/// The serializable contents of a [`FileProvider`], checksummed as a whole
+/// and persisted as `[sha256(payload) || payload]`.
+#[derive(Default, Serialize, Deserialize)]
+struct CacheContents {
+    full_blocks: HashMap<BlockQuery, Block<Transaction>>,
+    partial_blocks: HashMap<BlockQuery, Block<H256>>,
+    block_receipts: HashMap<BlockQuery, Vec<TransactionReceipt>>,
+    proofs: HashMap<ProofQuery, EIP1186AccountProofResponse>,
+    transaction_counts: HashMap<AccountQuery, U256>,
+    balances: HashMap<AccountQuery, U256>,
+    codes: HashMap<AccountQuery, Bytes>,
+    storage: HashMap<StorageQuery, H256>,
+    logs: HashMap<LogsQuery, Vec<Log>>,
+    transactions: HashMap<TxQuery, Transaction>,
+    blobs: HashMap<u64, GetBlobsResponse>,
+}
+
+impl CacheContents {
+    /// zstd compression level for the `BincodeZstd` codec. `3` is zstd's own
+    /// default: a good speed/ratio tradeoff for the repeated, structured
+    /// Ethereum primitives a witness cache holds.
+    const ZSTD_LEVEL: i32 = 3;
+
+    fn encode(&self, codec: CacheCodec) -> Result<Vec<u8>> {
+        match codec {
+            CacheCodec::Json => Ok(serde_json::to_vec(self)?),
+            CacheCodec::BincodeZstd => {
+                let packed = bincode::serialize(self)?;
+                Ok(zstd::stream::encode_all(&packed[..], Self::ZSTD_LEVEL)?)
+            }
+        }
+    }
+
+    fn decode(bytes: &[u8], codec: CacheCodec) -> Result<Self> {
+        match codec {
+            CacheCodec::Json => Ok(serde_json::from_slice(bytes)?),
+            CacheCodec::BincodeZstd => {
+                let packed = zstd::stream::decode_all(bytes)?;
+                Ok(bincode::deserialize(&packed)?)
+            }
+        }
+    }
+}
+
+/// A durable, file-backed [`Provider`]/[`MutProvider`] that holds its entire
+/// contents resident in memory and persists them to `cache_path` on `save()`.
+pub struct FileProvider {
+    cache_path: PathBuf,
+    codec: CacheCodec,
+    contents: CacheContents,
+}
+
+impl FileProvider {
+    /// Starts from an empty cache backed by `cache_path` (not yet written).
+    pub fn empty(cache_path: PathBuf, codec: CacheCodec) -> Self {
+        FileProvider {
+            cache_path,
+            codec,
+            contents: CacheContents::default(),
+        }
+    }
+
+    /// Loads a cache previously written by [`FileProvider::save`], verifying
+    /// its checksum. Returns [`CacheError::Missing`] if `cache_path` doesn't
+    /// exist and [`CacheError::Corrupted`] if it exists but fails the
+    /// checksum or otherwise fails to decode.
+    pub fn from_file(cache_path: &PathBuf, codec: CacheCodec) -> Result<Self, CacheError> {
+        let raw = std::fs::read(cache_path)?;
+        if raw.len() < CHECKSUM_LEN {
+            return Err(CacheError::Corrupted);
+        }
+
+        let (stored_checksum, payload) = raw.split_at(CHECKSUM_LEN);
+        let computed_checksum = Sha256::digest(payload);
+        if stored_checksum != computed_checksum.as_slice() {
+            return Err(CacheError::Corrupted);
+        }
+
+        let contents = CacheContents::decode(payload, codec).map_err(|_| CacheError::Corrupted)?;
+
+        Ok(FileProvider {
+            cache_path: cache_path.clone(),
+            codec,
+            contents,
+        })
+    }
+}
+
+impl Provider for FileProvider {
+    fn save(&self) -> Result<()> {
+        let payload = self.contents.encode(self.codec)?;
+        let checksum = Sha256::digest(&payload);
+
+        let mut out = Vec::with_capacity(CHECKSUM_LEN + payload.len());
+        out.extend_from_slice(&checksum);
+        out.extend_from_slice(&payload);
+
+        // Write to a temp file and rename over the target so a process that
+        // dies mid-save never leaves a half-written (and now, checksum-
+        // mismatched) cache in place of a good one.
+        let tmp_path = self.cache_path.with_extension("tmp");
+        std::fs::write(&tmp_path, out)?;
+        std::fs::rename(&tmp_path, &self.cache_path)?;
+        Ok(())
+    }
+
+    fn get_full_block(&mut self, query: &BlockQuery) -> Result<Block<Transaction>> {
+        self.contents
+            .full_blocks
+            .get(query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("full block not cached"))
+    }
+
+    fn get_partial_block(&mut self, query: &BlockQuery) -> Result<Block<H256>> {
+        self.contents
+            .partial_blocks
+            .get(query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("partial block not cached"))
+    }
+
+    fn get_block_receipts(&mut self, query: &BlockQuery) -> Result<Vec<TransactionReceipt>> {
+        self.contents
+            .block_receipts
+            .get(query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("block receipts not cached"))
+    }
+
+    fn get_proof(&mut self, query: &ProofQuery) -> Result<EIP1186AccountProofResponse> {
+        self.contents
+            .proofs
+            .get(query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("proof not cached"))
+    }
+
+    fn get_transaction_count(&mut self, query: &AccountQuery) -> Result<U256> {
+        self.contents
+            .transaction_counts
+            .get(query)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("transaction count not cached"))
+    }
+
+    fn get_balance(&mut self, query: &AccountQuery) -> Result<U256> {
+        self.contents
+            .balances
+            .get(query)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("balance not cached"))
+    }
+
+    fn get_code(&mut self, query: &AccountQuery) -> Result<Bytes> {
+        self.contents
+            .codes
+            .get(query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("code not cached"))
+    }
+
+    fn get_storage(&mut self, query: &StorageQuery) -> Result<H256> {
+        self.contents
+            .storage
+            .get(query)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("storage not cached"))
+    }
+
+    fn get_logs(&mut self, query: &LogsQuery) -> Result<Vec<Log>> {
+        self.contents
+            .logs
+            .get(query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("logs not cached"))
+    }
+
+    fn get_transaction(&mut self, query: &TxQuery) -> Result<Transaction> {
+        self.contents
+            .transactions
+            .get(query)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("transaction not cached"))
+    }
+
+    fn get_blob_data(&mut self, block_id: u64) -> Result<GetBlobsResponse> {
+        self.contents
+            .blobs
+            .get(&block_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("blob not cached"))
+    }
+}
+
+impl MutProvider for FileProvider {
+    fn insert_full_block(&mut self, query: BlockQuery, block: Block<Transaction>) {
+        self.contents.full_blocks.insert(query, block);
+    }
+
+    fn insert_partial_block(&mut self, query: BlockQuery, block: Block<H256>) {
+        self.contents.partial_blocks.insert(query, block);
+    }
+
+    fn insert_block_receipts(&mut self, query: BlockQuery, receipts: Vec<TransactionReceipt>) {
+        self.contents.block_receipts.insert(query, receipts);
+    }
+
+    fn insert_proof(&mut self, query: ProofQuery, proof: EIP1186AccountProofResponse) {
+        self.contents.proofs.insert(query, proof);
+    }
+
+    fn insert_transaction_count(&mut self, query: AccountQuery, count: U256) {
+        self.contents.transaction_counts.insert(query, count);
+    }
+
+    fn insert_balance(&mut self, query: AccountQuery, balance: U256) {
+        self.contents.balances.insert(query, balance);
+    }
+
+    fn insert_code(&mut self, query: AccountQuery, code: Bytes) {
+        self.contents.codes.insert(query, code);
+    }
+
+    fn insert_storage(&mut self, query: StorageQuery, value: H256) {
+        self.contents.storage.insert(query, value);
+    }
+
+    fn insert_logs(&mut self, query: LogsQuery, logs: Vec<Log>) {
+        self.contents.logs.insert(query, logs);
+    }
+
+    fn insert_transaction(&mut self, query: TxQuery, tx: Transaction) {
+        self.contents.transactions.insert(query, tx);
+    }
+
+    fn insert_blob(&mut self, block_id: u64, blob: GetBlobsResponse) {
+        self.contents.blobs.insert(block_id, blob);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::Address;
+
+    use super::*;
+
+    fn sample_contents() -> CacheContents {
+        let mut contents = CacheContents::default();
+        contents.balances.insert(
+            AccountQuery {
+                block_no: 42,
+                address: Address::zero(),
+            },
+            U256::from(1234),
+        );
+        contents
+    }
+
+    #[test]
+    fn json_codec_round_trips() {
+        let contents = sample_contents();
+        let encoded = contents.encode(CacheCodec::Json).unwrap();
+        let decoded = CacheContents::decode(&encoded, CacheCodec::Json).unwrap();
+        assert_eq!(decoded.balances, contents.balances);
+    }
+
+    #[test]
+    fn bincode_zstd_codec_round_trips() {
+        let contents = sample_contents();
+        let encoded = contents.encode(CacheCodec::BincodeZstd).unwrap();
+        let decoded = CacheContents::decode(&encoded, CacheCodec::BincodeZstd).unwrap();
+        assert_eq!(decoded.balances, contents.balances);
+    }
+
+    #[test]
+    fn from_file_detects_checksum_corruption() {
+        let path = std::env::temp_dir().join(format!(
+            "raiko_file_provider_corruption_{}.json",
+            std::process::id()
+        ));
+
+        let provider = FileProvider::empty(path.clone(), CacheCodec::Json);
+        provider.save().unwrap();
+
+        let mut raw = std::fs::read(&path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0xff;
+        std::fs::write(&path, &raw).unwrap();
+
+        let err = FileProvider::from_file(&path, CacheCodec::Json).unwrap_err();
+        assert!(matches!(err, CacheError::Corrupted));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod cached_rpc_provider;
+pub mod file_provider;
+pub mod rpc_provider;
+
+use alloy_rpc_types::EIP1186AccountProofResponse;
+use anyhow::Result;
+use ethers_core::types::{Address, Block, Bytes, Log, Transaction, TransactionReceipt, H256, U256};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockQuery {
+    pub block_no: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProofQuery {
+    pub block_no: u64,
+    pub address: Address,
+    pub indices: Vec<H256>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AccountQuery {
+    pub block_no: u64,
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StorageQuery {
+    pub block_no: u64,
+    pub address: Address,
+    pub index: H256,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LogsQuery {
+    pub block_no: u64,
+    pub address: Address,
+    pub topics: Vec<H256>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TxQuery {
+    pub block_no: Option<u64>,
+    pub tx_hash: H256,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GetBlobsResponse {
+    pub data: Vec<Bytes>,
+}
+
+/// A source of Ethereum chain data for witness collection: full/partial
+/// blocks, receipts, account/storage proofs, balances, code, logs,
+/// transactions and blob data.
+pub trait Provider {
+    fn save(&self) -> Result<()>;
+
+    fn get_full_block(&mut self, query: &BlockQuery) -> Result<Block<Transaction>>;
+    fn get_partial_block(&mut self, query: &BlockQuery) -> Result<Block<H256>>;
+    fn get_block_receipts(&mut self, query: &BlockQuery) -> Result<Vec<TransactionReceipt>>;
+    fn get_proof(&mut self, query: &ProofQuery) -> Result<EIP1186AccountProofResponse>;
+    fn get_transaction_count(&mut self, query: &AccountQuery) -> Result<U256>;
+    fn get_balance(&mut self, query: &AccountQuery) -> Result<U256>;
+    fn get_code(&mut self, query: &AccountQuery) -> Result<Bytes>;
+    fn get_storage(&mut self, query: &StorageQuery) -> Result<H256>;
+    fn get_logs(&mut self, query: &LogsQuery) -> Result<Vec<Log>>;
+    fn get_transaction(&mut self, query: &TxQuery) -> Result<Transaction>;
+    fn get_blob_data(&mut self, block_id: u64) -> Result<GetBlobsResponse>;
+
+    /// Batched form of [`Provider::get_proof`]. The default implementation
+    /// just loops so every `Provider` keeps compiling; implementations that
+    /// can pack requests onto a single round-trip should override it.
+    fn get_proofs(&mut self, queries: &[ProofQuery]) -> Result<Vec<EIP1186AccountProofResponse>> {
+        queries.iter().map(|query| self.get_proof(query)).collect()
+    }
+
+    /// Batched form of [`Provider::get_storage`].
+    fn get_storage_many(&mut self, queries: &[StorageQuery]) -> Result<Vec<H256>> {
+        queries.iter().map(|query| self.get_storage(query)).collect()
+    }
+
+    /// Batched form of [`Provider::get_code`].
+    fn get_codes(&mut self, queries: &[AccountQuery]) -> Result<Vec<Bytes>> {
+        queries.iter().map(|query| self.get_code(query)).collect()
+    }
+}
+
+/// The write side of a [`Provider`], used to populate a cache from results
+/// fetched elsewhere (typically from another `Provider`).
+pub trait MutProvider: Provider {
+    fn insert_full_block(&mut self, query: BlockQuery, block: Block<Transaction>);
+    fn insert_partial_block(&mut self, query: BlockQuery, block: Block<H256>);
+    fn insert_block_receipts(&mut self, query: BlockQuery, receipts: Vec<TransactionReceipt>);
+    fn insert_proof(&mut self, query: ProofQuery, proof: EIP1186AccountProofResponse);
+    fn insert_transaction_count(&mut self, query: AccountQuery, count: U256);
+    fn insert_balance(&mut self, query: AccountQuery, balance: U256);
+    fn insert_code(&mut self, query: AccountQuery, code: Bytes);
+    fn insert_storage(&mut self, query: StorageQuery, value: H256);
+    fn insert_logs(&mut self, query: LogsQuery, logs: Vec<Log>);
+    fn insert_transaction(&mut self, query: TxQuery, tx: Transaction);
+    fn insert_blob(&mut self, block_id: u64, blob: GetBlobsResponse);
+}
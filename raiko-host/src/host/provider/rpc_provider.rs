@@ -0,0 +1,283 @@
+// Copyright 2023 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy_rpc_types::EIP1186AccountProofResponse;
+use anyhow::{anyhow, Context, Result};
+use ethers_core::types::{
+    Block, BlockId, BlockNumber, Bytes, Log, Transaction, TransactionReceipt, H256, U256,
+};
+use ethers_providers::{Http, Middleware, Provider as EthersProvider};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+use super::{
+    AccountQuery, BlockQuery, GetBlobsResponse, LogsQuery, Provider, ProofQuery, StorageQuery,
+    TxQuery,
+};
+
+/// An Ethereum data source backed by a live JSON-RPC endpoint.
+pub struct RpcProvider {
+    rpc_url: String,
+    #[allow(dead_code)]
+    beacon_rpc_url: Option<String>,
+    client: EthersProvider<Http>,
+    http: reqwest::blocking::Client,
+    runtime: Runtime,
+}
+
+impl RpcProvider {
+    pub fn new(rpc_url: String, beacon_rpc_url: Option<String>) -> Result<Self> {
+        let client = EthersProvider::<Http>::try_from(rpc_url.as_str())
+            .context("invalid RPC URL")?;
+        let runtime = Runtime::new().context("failed to start RPC runtime")?;
+
+        Ok(RpcProvider {
+            rpc_url,
+            beacon_rpc_url,
+            client,
+            http: reqwest::blocking::Client::new(),
+            runtime,
+        })
+    }
+
+    /// Sends `calls` (method, params) as a single JSON-RPC batch POST and
+    /// returns each result in the same order, matched up by request id.
+    fn batch_call(&self, calls: &[(&str, Value)]) -> Result<Vec<Value>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body: Vec<Value> = calls
+            .iter()
+            .enumerate()
+            .map(|(id, (method, params))| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": method,
+                    "params": params,
+                })
+            })
+            .collect();
+
+        let response: Vec<Value> = self
+            .http
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .context("RPC batch request failed")?
+            .json()
+            .context("RPC batch response was not valid JSON")?;
+
+        let mut results = vec![None; calls.len()];
+        for entry in response {
+            let id = entry["id"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("RPC batch response entry missing id"))? as usize;
+            if let Some(err) = entry.get("error") {
+                return Err(anyhow!("RPC batch call {id} failed: {err}"));
+            }
+            let slot = results
+                .get_mut(id)
+                .ok_or_else(|| anyhow!("RPC batch response entry id {id} out of range for {} calls", calls.len()))?;
+            *slot = Some(entry["result"].clone());
+        }
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(id, result)| {
+                result.ok_or_else(|| anyhow!("RPC batch response missing entry for call {id}"))
+            })
+            .collect()
+    }
+}
+
+impl Provider for RpcProvider {
+    fn save(&self) -> Result<()> {
+        // Nothing to persist; RpcProvider always talks to the live endpoint.
+        Ok(())
+    }
+
+    fn get_full_block(&mut self, query: &BlockQuery) -> Result<Block<Transaction>> {
+        self.runtime.block_on(async {
+            self.client
+                .get_block_with_txs(query.block_no)
+                .await?
+                .ok_or_else(|| anyhow!("block {} not found", query.block_no))
+        })
+    }
+
+    fn get_partial_block(&mut self, query: &BlockQuery) -> Result<Block<H256>> {
+        self.runtime.block_on(async {
+            self.client
+                .get_block(query.block_no)
+                .await?
+                .ok_or_else(|| anyhow!("block {} not found", query.block_no))
+        })
+    }
+
+    fn get_block_receipts(&mut self, query: &BlockQuery) -> Result<Vec<TransactionReceipt>> {
+        self.runtime.block_on(async {
+            Ok(self
+                .client
+                .get_block_receipts(query.block_no)
+                .await?)
+        })
+    }
+
+    fn get_proof(&mut self, query: &ProofQuery) -> Result<EIP1186AccountProofResponse> {
+        let [result] = self.get_proofs(std::slice::from_ref(query))?.try_into()
+            .map_err(|_| anyhow!("expected exactly one proof"))?;
+        Ok(result)
+    }
+
+    fn get_transaction_count(&mut self, query: &AccountQuery) -> Result<U256> {
+        self.runtime.block_on(async {
+            Ok(self
+                .client
+                .get_transaction_count(query.address, Some(BlockId::from(query.block_no)))
+                .await?)
+        })
+    }
+
+    fn get_balance(&mut self, query: &AccountQuery) -> Result<U256> {
+        self.runtime.block_on(async {
+            Ok(self
+                .client
+                .get_balance(query.address, Some(BlockId::from(query.block_no)))
+                .await?)
+        })
+    }
+
+    fn get_code(&mut self, query: &AccountQuery) -> Result<Bytes> {
+        let [result] = self.get_codes(std::slice::from_ref(query))?.try_into()
+            .map_err(|_| anyhow!("expected exactly one code"))?;
+        Ok(result)
+    }
+
+    fn get_storage(&mut self, query: &StorageQuery) -> Result<H256> {
+        let [result] = self.get_storage_many(std::slice::from_ref(query))?.try_into()
+            .map_err(|_| anyhow!("expected exactly one storage value"))?;
+        Ok(result)
+    }
+
+    fn get_logs(&mut self, query: &LogsQuery) -> Result<Vec<Log>> {
+        self.runtime.block_on(async {
+            let filter = ethers_core::types::Filter::new()
+                .select(query.block_no)
+                .address(query.address)
+                .topic0(query.topics.clone());
+            Ok(self.client.get_logs(&filter).await?)
+        })
+    }
+
+    fn get_transaction(&mut self, query: &TxQuery) -> Result<Transaction> {
+        self.runtime.block_on(async {
+            self.client
+                .get_transaction(query.tx_hash)
+                .await?
+                .ok_or_else(|| anyhow!("transaction {:?} not found", query.tx_hash))
+        })
+    }
+
+    fn get_blob_data(&mut self, block_id: u64) -> Result<GetBlobsResponse> {
+        let _ = block_id;
+        Err(anyhow!(
+            "RpcProvider does not fetch blob data, whether or not a beacon RPC URL is configured"
+        ))
+    }
+
+    fn get_proofs(&mut self, queries: &[ProofQuery]) -> Result<Vec<EIP1186AccountProofResponse>> {
+        let calls: Vec<(&str, Value)> = queries
+            .iter()
+            .map(|query| {
+                (
+                    "eth_getProof",
+                    json!([
+                        query.address,
+                        query.indices,
+                        BlockNumber::Number(query.block_no.into()),
+                    ]),
+                )
+            })
+            .collect();
+
+        self.batch_call(&calls)?
+            .into_iter()
+            .map(|result| {
+                Ok(serde_json::from_value::<EIP1186AccountProofResponse>(result)?)
+            })
+            .collect()
+    }
+
+    fn get_storage_many(&mut self, queries: &[StorageQuery]) -> Result<Vec<H256>> {
+        let calls: Vec<(&str, Value)> = queries
+            .iter()
+            .map(|query| {
+                (
+                    "eth_getStorageAt",
+                    json!([
+                        query.address,
+                        query.index,
+                        BlockNumber::Number(query.block_no.into()),
+                    ]),
+                )
+            })
+            .collect();
+
+        self.batch_call(&calls)?
+            .into_iter()
+            .map(|result| Ok(serde_json::from_value::<H256>(result)?))
+            .collect()
+    }
+
+    fn get_codes(&mut self, queries: &[AccountQuery]) -> Result<Vec<Bytes>> {
+        let calls: Vec<(&str, Value)> = queries
+            .iter()
+            .map(|query| {
+                (
+                    "eth_getCode",
+                    json!([query.address, BlockNumber::Number(query.block_no.into())]),
+                )
+            })
+            .collect();
+
+        self.batch_call(&calls)?
+            .into_iter()
+            .map(|result| Ok(serde_json::from_value::<Bytes>(result)?))
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct FinalizedBlockHeader {
+    number: U256,
+}
+
+impl RpcProvider {
+    /// Queries the chain's current finalized block number via the
+    /// `"finalized"` block tag, so callers can tell which cached entries are
+    /// still safe to trust across a reorg.
+    pub fn get_finalized_block_number(&mut self) -> Result<u64> {
+        self.runtime.block_on(async {
+            let header: FinalizedBlockHeader = self
+                .client
+                .request("eth_getBlockByNumber", ("finalized", false))
+                .await?;
+            Ok(header.number.as_u64())
+        })
+    }
+}